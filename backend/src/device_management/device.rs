@@ -1,138 +1,229 @@
-use regex::Regex;
 use rsmobiledevice::{
-    device::Event,
+    device::{DeviceClient, Event},
     device_info::{domains::DeviceDomains, keys::DeviceKeys},
-    device_syslog::{filters::FilterPart, LogFilter},
+    device_syslog::filters::FilterPart,
     RecursiveFind,
 };
+use serde::Serialize;
+use std::collections::HashMap;
 use std::sync::Arc;
-use tauri::Emitter;
+use tauri::{Emitter, Manager, State};
 
+use super::events::emit_for_device;
 use super::handlers::{
-    battery::handle_device_battery, hardware::handle_device_hardware, os::handle_device_os,
-    storage::handle_device_storage,
+    battery::handle_device_battery, cellular::handle_device_cellular, hardware::handle_device_hardware,
+    os::handle_device_os, storage::handle_device_storage,
 };
+use super::known_devices::{CachedDeviceProfile, KnownDevicesStore};
+use super::provisioning;
+use super::registry::{lock_device, DeviceRegistry};
+use super::syslog::run_oneshot_filter;
+
+/// One update out of the carrier bundle installer's status dictionary:
+/// the current phase name, plus `PercentComplete` when the installer
+/// reports one.
+#[derive(Serialize, Clone, Debug)]
+pub struct CarrierBundleInstallProgress {
+    pub phase: String,
+    pub percent_complete: Option<f64>,
+}
+
+impl CarrierBundleInstallProgress {
+    fn from_status(status: &HashMap<String, String>) -> Self {
+        Self {
+            phase: status
+                .get("Status")
+                .cloned()
+                .unwrap_or_else(|| "Unknown".to_string()),
+            percent_complete: status.get("PercentComplete").and_then(|p| p.parse().ok()),
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.phase == "Completed"
+    }
+}
+
+/// Terminal outcome of a `install_ipcc` run, replacing the old bare
+/// `true`/`false` so the frontend can surface *why* an install failed.
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum CarrierBundleInstallStatus {
+    Success,
+    Failure { error: String },
+}
 
 #[tauri::command]
-pub fn install_ipcc(window: tauri::Window, device_model: String, ios_ver: String) {
-    let device_client_res = rsmobiledevice::device::DeviceClient::new().and_then(|client| {
-        client
-            .get_first_device()
-            .ok_or(rsmobiledevice::errors::DeviceClientError::DeviceNotFound)
-    });
+pub fn install_ipcc(
+    window: tauri::Window,
+    registry: State<DeviceRegistry>,
+    udid: String,
+    device_model: String,
+    ios_ver: String,
+    mcc: String,
+    mnc: String,
+) {
+    let device_handle = match registry.get(&udid) {
+        Some(device_handle) => device_handle,
+        None => {
+            let error = format!("No tracked device for UDID {udid}");
+            log::error!("{error}");
+            emit_for_device(
+                &window,
+                "carrier_bundle_install_status",
+                &udid,
+                &CarrierBundleInstallStatus::Failure { error },
+            );
+            return;
+        }
+    };
 
-    match device_client_res {
-        Ok(device_client) => {
-            std::thread::spawn(move || {
-                let device_info = device_client.get_device_info();
-
-                let connected_model = device_info
-                    .get_value(DeviceKeys::ProductType, DeviceDomains::All)
-                    .unwrap_or_default();
-                let connected_ios_ver = device_info
-                    .get_value(DeviceKeys::ProductVersion, DeviceDomains::All)
-                    .unwrap_or_default();
-
-                if device_model != connected_model || ios_ver != connected_ios_ver {
-                    log::info!(
-                        "Model or iOS version mismatch: expected {}:{}, got {}:{}",
-                        device_model,
-                        ios_ver,
-                        connected_model,
-                        connected_ios_ver
-                    );
-                    window.emit("carrier_bundle_install_status", false).ok();
-                    return;
-                }
+    std::thread::spawn(move || {
+        // Held for the whole install so a concurrent `check_installing_succeed`
+        // (or a second install) on the same UDID blocks on this device's
+        // connection instead of racing it.
+        let device_client = lock_device(&device_handle);
+        let device_info = device_client.get_device_info();
 
-                let window_clone = window.clone();
+        let connected_model = device_info
+            .get_value(DeviceKeys::ProductType, DeviceDomains::All)
+            .unwrap_or_default();
+        let connected_ios_ver = device_info
+            .get_value(DeviceKeys::ProductVersion, DeviceDomains::All)
+            .unwrap_or_default();
 
-                let install_client = device_client.get_device_installer();
+        if device_model != connected_model || ios_ver != connected_ios_ver {
+            let error = format!(
+                "Model or iOS version mismatch: expected {device_model}:{ios_ver}, got {connected_model}:{connected_ios_ver}"
+            );
+            log::info!("{error}");
+            emit_for_device(
+                &window,
+                "carrier_bundle_install_status",
+                &udid,
+                &CarrierBundleInstallStatus::Failure { error },
+            );
+            return;
+        }
 
-                // this will be replaced with an api call
-                if let Err(e) = install_client.install_from_path_with_callback(
-                    "~/y.ipcc",
-                    None,
-                    move |_, status| {
-                        if status.rfind("Status").is_some_and(|s| &s == "Completed") {
-                            window_clone
-                                .emit("carrier_bundle_install_status", true)
-                                .ok();
-                        }
-                    },
-                ) {
-                    log::error!("Installation failed: {}", e);
-                    window.emit("carrier_bundle_install_status", true).ok();
-                } else {
-                    log::info!("IPCC installation started");
+        let bundle_path = match provisioning::resolve_bundle_path(
+            &window.app_handle(),
+            &connected_model,
+            &connected_ios_ver,
+            &mcc,
+            &mnc,
+        ) {
+            Ok(path) => path,
+            Err(error) => {
+                log::error!("Failed to resolve carrier bundle: {error}");
+                emit_for_device(
+                    &window,
+                    "carrier_bundle_install_status",
+                    &udid,
+                    &CarrierBundleInstallStatus::Failure { error },
+                );
+                return;
+            }
+        };
+
+        let window_clone = window.clone();
+        let udid_clone = udid.clone();
+
+        let install_client = device_client.get_device_installer();
+
+        if let Err(e) = install_client.install_from_path_with_callback(
+            bundle_path.to_string_lossy().as_ref(),
+            None,
+            move |_, status| {
+                let progress = CarrierBundleInstallProgress::from_status(&status);
+                emit_for_device(
+                    &window_clone,
+                    "carrier_bundle_install_progress",
+                    &udid_clone,
+                    &progress,
+                );
+
+                if progress.is_complete() {
+                    emit_for_device(
+                        &window_clone,
+                        "carrier_bundle_install_status",
+                        &udid_clone,
+                        &CarrierBundleInstallStatus::Success,
+                    );
                 }
-            });
+            },
+        ) {
+            log::error!("Installation failed: {}", e);
+            emit_for_device(
+                &window,
+                "carrier_bundle_install_status",
+                &udid,
+                &CarrierBundleInstallStatus::Failure { error: e.to_string() },
+            );
+        } else {
+            log::info!("IPCC installation started");
         }
-        Err(client_error) => {
-            log::error!("Failed to initialize device client: {}", client_error);
-            window.emit("carrier_bundle_install_status", false).ok();
-        }
-    }
+    });
 }
 
 #[tauri::command]
-pub fn check_installing_succeed(window: tauri::Window) {
-    let device_client_res = rsmobiledevice::device::DeviceClient::new().and_then(|client| {
-        client
-            .get_first_device()
-            .ok_or(rsmobiledevice::errors::DeviceClientError::DeviceNotFound)
-    });
+pub fn check_installing_succeed(window: tauri::Window, registry: State<DeviceRegistry>, udid: String) {
+    let device_handle = match registry.get(&udid) {
+        Some(device_handle) => device_handle,
+        None => {
+            log::error!("No tracked device for UDID {udid}");
+            emit_for_device(&window, "installation_succeed_status", &udid, &false);
+            return;
+        }
+    };
+    // Locked for the duration of the scan so it blocks until any in-flight
+    // `install_ipcc` on the same UDID releases the device's connection.
+    let device_client = lock_device(&device_handle);
 
-    match device_client_res {
-        Ok(device_client) => {
-            let mut syslog_client = device_client.get_device_syslog();
+    let window = Arc::new(window);
+    let udid_1 = udid.clone();
+    let udid_2 = udid.clone();
 
-            match Regex::new(r"/\b\w*SIM is Ready\w*\b/i") {
-                Ok(re) => {
-                    // usually there will be a message about the sim being ready in the logs if the carrier
-                    // bundle installation is good
-                    syslog_client.set_filter(LogFilter::OneShot(re), FilterPart::All);
-                }
-                Err(e) => {
-                    log::error!("Failed to create a new regex, error: {e}");
-                    window.emit("installation_succeed_status", false).ok();
-                    return;
-                }
-            }
+    let window_1 = Arc::clone(&window);
+    let window_2 = Arc::clone(&window);
 
-            let window = Arc::new(window);
-
-            let window_1 = Arc::clone(&window);
-            let window_2 = Arc::clone(&window);
-
-            // the first callback should be called once the filter succeed to be found and it will
-            // stop because we specifed the OneShot, which basically stops the logging if the
-            // filter applied
-            //
-            // if not and it exceeded the timeout, the second callback would get called, thus
-            // triggering the false payload
-            if let Err(e) = syslog_client.log_to_custom_with_timeout_or_else(
-                move |_| {
-                    log::info!("SIM ready detected");
-                    window_1.emit("installation_succeed_status", true).ok();
-                },
-                std::time::Duration::from_secs(40),
-                move || {
-                    log::warn!("SIM ready not detected within 40s");
-                    window_2.emit("installation_succeed_status", false).ok();
-                },
-            ) {
-                log::error!("Syslog monitoring failed: {}", e);
-                window.emit("installation_succeed_status", false).ok();
-            }
-        }
-        Err(e) => {
-            log::error!("Failed to initialize device client: {}", e);
-            window.emit("installation_succeed_status", false).ok();
-        }
+    // usually there will be a message about the sim being ready in the logs if the carrier
+    // bundle installation is good
+    //
+    // the first callback should be called once the filter succeed to be found and it will
+    // stop because we specifed the OneShot, which basically stops the logging if the
+    // filter applied
+    //
+    // if not and it exceeded the timeout, the second callback would get called, thus
+    // triggering the false payload
+    if let Err(e) = run_oneshot_filter(
+        &device_client,
+        r"(?i)SIM is Ready",
+        FilterPart::All,
+        std::time::Duration::from_secs(40),
+        move |_| {
+            log::info!("SIM ready detected");
+            emit_for_device(&window_1, "installation_succeed_status", &udid_1, &true);
+        },
+        move || {
+            log::warn!("SIM ready not detected within 40s");
+            emit_for_device(&window_2, "installation_succeed_status", &udid_2, &false);
+        },
+    ) {
+        log::error!("Syslog monitoring failed: {}", e);
+        emit_for_device(&window, "installation_succeed_status", &udid, &false);
     }
 }
 
+/// UDID of every device `rsmobiledevice` can currently see, alongside a
+/// ready-to-use client for it.
+fn connected_devices() -> Vec<(String, DeviceClient)> {
+    DeviceClient::recursive_find()
+        .into_iter()
+        .map(|device_client| (device_client.get_udid(), device_client))
+        .collect()
+}
+
 #[tauri::command]
 pub fn check_device(window: tauri::Window) {
     window.emit("device_status", false).ok();
@@ -143,36 +234,133 @@ pub fn check_device(window: tauri::Window) {
             log::info!("device connected");
             window.emit("device_status", true).ok();
 
-            let device_client = rsmobiledevice::device::DeviceClient::new()
-                .and_then(|client| {
-                    client
-                        .get_first_device()
-                        .ok_or(rsmobiledevice::errors::DeviceClientError::DeviceNotFound)
-                })
-                .unwrap();
-
-            window
-                .emit("device_hardware", handle_device_hardware(&device_client))
-                .ok();
-
-            window
-                .emit("device_storage", handle_device_storage(&device_client))
-                .ok();
-
-            window
-                .emit("device_battery", handle_device_battery(&device_client))
-                .ok();
-
-            window
-                .emit("device_os", handle_device_os(&device_client))
-                .ok();
+            let registry = window.state::<DeviceRegistry>();
+            let known_devices = window.state::<KnownDevicesStore>();
+            let app_handle = window.app_handle().clone();
+            let connected = connected_devices();
+            let current_udids: Vec<String> = connected.iter().map(|(udid, _)| udid.clone()).collect();
+
+            for (udid, device_client) in connected {
+                if registry.udids().contains(&udid) {
+                    continue;
+                }
+
+                if let Some(known) = known_devices.get(&udid) {
+                    log::info!("known device {udid} reconnected");
+                    emit_for_device(&window, "device_reconnected", &udid, &known);
+
+                    if let Some(hardware) = known.profile.hardware.as_ref() {
+                        emit_for_device(&window, "device_hardware", &udid, hardware);
+                    }
+                    if let Some(storage) = known.profile.storage.as_ref() {
+                        emit_for_device(&window, "device_storage", &udid, storage);
+                    }
+                    if let Some(battery) = known.profile.battery.as_ref() {
+                        emit_for_device(&window, "device_battery", &udid, battery);
+                    }
+                    if let Some(os) = known.profile.os.as_ref() {
+                        emit_for_device(&window, "device_os", &udid, os);
+                    }
+                    if let Some(cellular) = known.profile.cellular.as_ref() {
+                        emit_for_device(&window, "device_cellular", &udid, cellular);
+                    }
+                }
+
+                let hardware = handle_device_hardware(&device_client);
+                let storage = handle_device_storage(&device_client);
+                let battery = handle_device_battery(&device_client);
+                let os = handle_device_os(&device_client);
+                let cellular = handle_device_cellular(&device_client);
+
+                emit_for_device(&window, "device_hardware", &udid, &hardware);
+                emit_for_device(&window, "device_storage", &udid, &storage);
+                emit_for_device(&window, "device_battery", &udid, &battery);
+                emit_for_device(&window, "device_os", &udid, &os);
+                emit_for_device(&window, "device_cellular", &udid, &cellular);
+
+                let friendly_name = device_client
+                    .get_device_info()
+                    .get_value(DeviceKeys::DeviceName, DeviceDomains::All);
+
+                known_devices.remember(
+                    &app_handle,
+                    udid.clone(),
+                    friendly_name,
+                    CachedDeviceProfile {
+                        hardware: serde_json::to_value(&hardware).ok(),
+                        storage: serde_json::to_value(&storage).ok(),
+                        battery: serde_json::to_value(&battery).ok(),
+                        os: serde_json::to_value(&os).ok(),
+                        cellular: serde_json::to_value(&cellular).ok(),
+                    },
+                );
+
+                registry.insert(udid, device_client);
+            }
+
+            registry.retain_connected(&current_udids);
         }
         Event::Disconnect => {
             println!("disconnected");
             log::info!("device disconnected");
-            window.emit("device_status", false).ok();
+
+            let registry = window.state::<DeviceRegistry>();
+            let current_udids: Vec<String> =
+                connected_devices().into_iter().map(|(udid, _)| udid).collect();
+            let removed = registry.retain_connected(&current_udids);
+            for udid in &removed {
+                log::info!("device {udid} disconnected");
+            }
+
+            if registry.udids().is_empty() {
+                window.emit("device_status", false).ok();
+            }
         }
         Event::Pair => {}
     })
     .unwrap();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_status_defaults_phase_when_missing() {
+        let progress = CarrierBundleInstallProgress::from_status(&HashMap::new());
+        assert_eq!(progress.phase, "Unknown");
+        assert_eq!(progress.percent_complete, None);
+    }
+
+    #[test]
+    fn from_status_reads_phase_and_percent_complete() {
+        let mut status = HashMap::new();
+        status.insert("Status".to_string(), "Installing".to_string());
+        status.insert("PercentComplete".to_string(), "42".to_string());
+
+        let progress = CarrierBundleInstallProgress::from_status(&status);
+
+        assert_eq!(progress.phase, "Installing");
+        assert_eq!(progress.percent_complete, Some(42.0));
+    }
+
+    #[test]
+    fn from_status_ignores_unparseable_percent_complete() {
+        let mut status = HashMap::new();
+        status.insert("PercentComplete".to_string(), "not-a-number".to_string());
+
+        let progress = CarrierBundleInstallProgress::from_status(&status);
+
+        assert_eq!(progress.percent_complete, None);
+    }
+
+    #[test]
+    fn is_complete_only_when_phase_is_completed() {
+        let mut status = HashMap::new();
+        status.insert("Status".to_string(), "Completed".to_string());
+        assert!(CarrierBundleInstallProgress::from_status(&status).is_complete());
+
+        status.insert("Status".to_string(), "Installing".to_string());
+        assert!(!CarrierBundleInstallProgress::from_status(&status).is_complete());
+    }
+}
@@ -0,0 +1,24 @@
+use serde::Serialize;
+use tauri::Emitter;
+
+/// Wraps a per-device payload with the UDID it belongs to, so the frontend
+/// can tell which phone an event (e.g. `device_hardware`) is about even
+/// though several may be connected at once.
+#[derive(Serialize)]
+struct DeviceEvent<'a, T> {
+    udid: String,
+    data: &'a T,
+}
+
+pub fn emit_for_device<T: Serialize>(window: &tauri::Window, event: &str, udid: &str, data: &T) {
+    window
+        .emit_to(
+            window.label(),
+            event,
+            DeviceEvent {
+                udid: udid.to_string(),
+                data,
+            },
+        )
+        .ok();
+}
@@ -0,0 +1,35 @@
+use rsmobiledevice::{
+    device::DeviceClient,
+    device_info::{domains::DeviceDomains, keys::DeviceKeys},
+};
+use serde::Serialize;
+
+/// Modem/SIM state for a device, read through the lockdown keys that expose
+/// the cellular subsystem. Fields are `None` when the device has no modem
+/// (e.g. Wi-Fi-only iPads) or the value isn't exposed by the domain.
+///
+/// Only keys that actually exist on `DeviceKeys` are read here, all via
+/// `DeviceDomains::All` like every other call site in this codebase —
+/// carrier name, APN, signal strength and roaming state are not properties
+/// libimobiledevice exposes through lockdown, so there's nothing to read
+/// for them.
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct DeviceCellular {
+    pub imei: Option<String>,
+    pub iccid: Option<String>,
+    pub meid: Option<String>,
+    pub mcc: Option<String>,
+    pub mnc: Option<String>,
+}
+
+pub fn handle_device_cellular(device_client: &DeviceClient) -> DeviceCellular {
+    let device_info = device_client.get_device_info();
+
+    DeviceCellular {
+        imei: device_info.get_value(DeviceKeys::InternationalMobileEquipmentIdentity, DeviceDomains::All),
+        iccid: device_info.get_value(DeviceKeys::IntegratedCircuitCardIdentity, DeviceDomains::All),
+        meid: device_info.get_value(DeviceKeys::MobileEquipmentIdentifier, DeviceDomains::All),
+        mcc: device_info.get_value(DeviceKeys::MobileSubscriberCountryCode, DeviceDomains::All),
+        mnc: device_info.get_value(DeviceKeys::MobileSubscriberNetworkCode, DeviceDomains::All),
+    }
+}
@@ -0,0 +1,5 @@
+pub mod battery;
+pub mod cellular;
+pub mod hardware;
+pub mod os;
+pub mod storage;
@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::Manager;
+
+/// The last hardware/storage/battery/os/cellular payloads seen for a
+/// device, cached as raw JSON so we don't need to know the concrete handler
+/// return types to store them.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CachedDeviceProfile {
+    pub hardware: Option<serde_json::Value>,
+    pub storage: Option<serde_json::Value>,
+    pub battery: Option<serde_json::Value>,
+    pub os: Option<serde_json::Value>,
+    pub cellular: Option<serde_json::Value>,
+}
+
+impl CachedDeviceProfile {
+    /// Layers `new` over `self`, keeping a field from `self` wherever `new`
+    /// didn't supply one (e.g. a handler's `serde_json::to_value` failed)
+    /// instead of losing the last known good value for it.
+    fn merge(self, new: CachedDeviceProfile) -> Self {
+        Self {
+            hardware: new.hardware.or(self.hardware),
+            storage: new.storage.or(self.storage),
+            battery: new.battery.or(self.battery),
+            os: new.os.or(self.os),
+            cellular: new.cellular.or(self.cellular),
+        }
+    }
+}
+
+/// A device the user has previously worked with.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KnownDevice {
+    pub udid: String,
+    pub friendly_name: String,
+    pub last_seen_unix: u64,
+    #[serde(default)]
+    pub profile: CachedDeviceProfile,
+}
+
+fn store_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join("known_devices.json"))
+}
+
+fn load_devices(path: &Path) -> HashMap<String, KnownDevice> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str::<Vec<KnownDevice>>(&contents).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|device| (device.udid.clone(), device))
+        .collect()
+}
+
+fn persist_devices(path: &Path, devices: &HashMap<String, KnownDevice>) {
+    let devices: Vec<&KnownDevice> = devices.values().collect();
+    if let Ok(json) = serde_json::to_string_pretty(&devices) {
+        let _ = fs::write(path, json);
+    }
+}
+
+/// Persisted registry of devices the user has previously worked with, keyed
+/// by UDID and backed by a JSON file in the app data dir. `check_device`
+/// upserts into this store on every connect; `remember` merges the newly
+/// fetched friendly name and profile fields over the previously cached ones
+/// rather than replacing them outright, so a field a handler failed to read
+/// or serialize this time around doesn't wipe out the last known good value
+/// for it.
+#[derive(Default)]
+pub struct KnownDevicesStore(Mutex<HashMap<String, KnownDevice>>);
+
+impl KnownDevicesStore {
+    pub fn load(app: &tauri::AppHandle) -> Self {
+        let devices = store_path(app)
+            .ok()
+            .map(|path| load_devices(&path))
+            .unwrap_or_default();
+
+        Self(Mutex::new(devices))
+    }
+
+    fn persist(&self, app: &tauri::AppHandle) {
+        let Ok(path) = store_path(app) else {
+            return;
+        };
+        persist_devices(&path, &self.0.lock().unwrap());
+    }
+
+    pub fn get(&self, udid: &str) -> Option<KnownDevice> {
+        self.0.lock().unwrap().get(udid).cloned()
+    }
+
+    /// Records `udid` as seen now, merging `friendly_name` and `profile`
+    /// over whatever was cached before, then persists the store to disk.
+    ///
+    /// `friendly_name` is `None` when the caller couldn't read the device's
+    /// name this time around; rather than falling back to a raw UDID and
+    /// clobbering a previously known name, the last known good name is kept,
+    /// same as `CachedDeviceProfile::merge` does for the profile fields.
+    pub fn remember(
+        &self,
+        app: &tauri::AppHandle,
+        udid: String,
+        friendly_name: Option<String>,
+        profile: CachedDeviceProfile,
+    ) {
+        let last_seen_unix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+
+        let mut devices = self.0.lock().unwrap();
+        let existing = devices.get(&udid);
+        let profile = match existing {
+            Some(existing) => existing.profile.clone().merge(profile),
+            None => profile,
+        };
+        let friendly_name = friendly_name
+            .or_else(|| existing.map(|existing| existing.friendly_name.clone()))
+            .unwrap_or_else(|| udid.clone());
+        devices.insert(
+            udid.clone(),
+            KnownDevice {
+                udid,
+                friendly_name,
+                last_seen_unix,
+                profile,
+            },
+        );
+        drop(devices);
+        self.persist(app);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_path() -> PathBuf {
+        super::super::test_support::scratch_dir("known_devices").join("known_devices.json")
+    }
+
+    fn device(udid: &str, profile: CachedDeviceProfile) -> KnownDevice {
+        KnownDevice {
+            udid: udid.to_string(),
+            friendly_name: "Test Device".to_string(),
+            last_seen_unix: 0,
+            profile,
+        }
+    }
+
+    #[test]
+    fn load_devices_is_empty_when_no_file_exists() {
+        assert!(load_devices(&scratch_path()).is_empty());
+    }
+
+    #[test]
+    fn persist_devices_then_load_devices_round_trips() {
+        let path = scratch_path();
+        let mut devices = HashMap::new();
+        devices.insert(
+            "udid-1".to_string(),
+            device(
+                "udid-1",
+                CachedDeviceProfile {
+                    hardware: Some(serde_json::json!({"model": "iPhone"})),
+                    ..Default::default()
+                },
+            ),
+        );
+
+        persist_devices(&path, &devices);
+        let reloaded = load_devices(&path);
+
+        let reloaded = reloaded.get("udid-1").expect("device should round-trip");
+        assert_eq!(reloaded.friendly_name, "Test Device");
+        assert_eq!(reloaded.profile.hardware, Some(serde_json::json!({"model": "iPhone"})));
+    }
+
+    #[test]
+    fn merge_keeps_previous_field_when_new_profile_lacks_it() {
+        let previous = CachedDeviceProfile {
+            hardware: Some(serde_json::json!({"model": "iPhone"})),
+            battery: Some(serde_json::json!({"level": 80})),
+            ..Default::default()
+        };
+        let new = CachedDeviceProfile {
+            hardware: None,
+            battery: Some(serde_json::json!({"level": 75})),
+            ..Default::default()
+        };
+
+        let merged = previous.merge(new);
+
+        assert_eq!(merged.hardware, Some(serde_json::json!({"model": "iPhone"})));
+        assert_eq!(merged.battery, Some(serde_json::json!({"level": 75})));
+    }
+
+    #[test]
+    fn merge_prefers_new_field_over_previous_when_both_are_set() {
+        let previous = CachedDeviceProfile {
+            hardware: Some(serde_json::json!({"model": "old"})),
+            ..Default::default()
+        };
+        let new = CachedDeviceProfile {
+            hardware: Some(serde_json::json!({"model": "new"})),
+            ..Default::default()
+        };
+
+        let merged = previous.merge(new);
+
+        assert_eq!(merged.hardware, Some(serde_json::json!({"model": "new"})));
+    }
+}
@@ -0,0 +1,9 @@
+pub mod device;
+pub mod events;
+pub mod handlers;
+pub mod known_devices;
+pub mod provisioning;
+pub mod registry;
+pub mod syslog;
+#[cfg(test)]
+pub(crate) mod test_support;
@@ -0,0 +1,246 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::Manager;
+
+const CATALOG_BASE_URL: &str = "https://catalog.openitool.app/v1";
+const CATALOG_REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// One entry in the carrier bundle catalog: a specific IPCC build for a
+/// given operator, keyed by MCC/MNC and the device model/iOS version it
+/// targets.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct CarrierBundleInfo {
+    pub id: String,
+    pub carrier_name: String,
+    pub mcc: String,
+    pub mnc: String,
+    pub product_type: String,
+    pub product_version: String,
+    pub download_url: String,
+    pub sha256: String,
+}
+
+/// Queries the remote catalog for every bundle available for the given
+/// device model/iOS version, so the UI can present a pick list instead of
+/// requiring a pre-staged file.
+pub fn list_available_bundles(
+    product_type: &str,
+    product_version: &str,
+) -> Result<Vec<CarrierBundleInfo>, String> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(CATALOG_REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    client
+        .get(format!("{CATALOG_BASE_URL}/bundles"))
+        .query(&[
+            ("product_type", product_type),
+            ("product_version", product_version),
+        ])
+        .send()
+        .and_then(|response| response.json::<Vec<CarrierBundleInfo>>())
+        .map_err(|e| {
+            if e.is_timeout() {
+                format!("Timed out reaching the carrier bundle catalog: {e}")
+            } else {
+                e.to_string()
+            }
+        })
+}
+
+/// Resolves the bundle matching `mcc`/`mnc` for `product_type`/`product_version`,
+/// downloading it into the app's cache dir (and verifying its checksum) if it
+/// isn't already cached, and returns the local path ready for the installer.
+///
+/// The catalog entry used to resolve a cached `.ipcc` is itself cached
+/// alongside it (see `cached_bundle_info`/`persist_bundle_info`), so a
+/// bundle installed on a previous run can be reused without the catalog
+/// being reachable at all.
+pub fn resolve_bundle_path(
+    app: &tauri::AppHandle,
+    product_type: &str,
+    product_version: &str,
+    mcc: &str,
+    mnc: &str,
+) -> Result<PathBuf, String> {
+    let cache_dir = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| e.to_string())?
+        .join("carrier_bundles");
+    fs::create_dir_all(&cache_dir).map_err(|e| e.to_string())?;
+
+    let cache_key = bundle_cache_key(product_type, product_version, mcc, mnc);
+
+    if let Some(cached_bundle) = cached_bundle_info(&cache_dir, &cache_key) {
+        let bundle_path = cache_dir.join(format!("{}.ipcc", cached_bundle.id));
+        if bundle_path.exists() && file_matches_checksum(&bundle_path, &cached_bundle.sha256)? {
+            return Ok(bundle_path);
+        }
+    }
+
+    let bundle = list_available_bundles(product_type, product_version)?
+        .into_iter()
+        .find(|bundle| bundle.mcc == mcc && bundle.mnc == mnc)
+        .ok_or_else(|| {
+            format!(
+                "No carrier bundle found for MCC {mcc}/MNC {mnc} on {product_type} {product_version}"
+            )
+        })?;
+
+    let bundle_path = cache_dir.join(format!("{}.ipcc", bundle.id));
+
+    if bundle_path.exists() && file_matches_checksum(&bundle_path, &bundle.sha256)? {
+        persist_bundle_info(&cache_dir, &cache_key, &bundle);
+        return Ok(bundle_path);
+    }
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(CATALOG_REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let bytes = client
+        .get(&bundle.download_url)
+        .send()
+        .and_then(|response| response.bytes())
+        .map_err(|e| {
+            if e.is_timeout() {
+                format!("Timed out downloading carrier bundle {}: {e}", bundle.id)
+            } else {
+                e.to_string()
+            }
+        })?;
+
+    let digest = sha256_hex(&bytes);
+    if digest != bundle.sha256 {
+        return Err(format!(
+            "Checksum mismatch for bundle {}: expected {}, got {digest}",
+            bundle.id, bundle.sha256
+        ));
+    }
+
+    fs::write(&bundle_path, &bytes).map_err(|e| e.to_string())?;
+    persist_bundle_info(&cache_dir, &cache_key, &bundle);
+    Ok(bundle_path)
+}
+
+/// Identifies the (product, carrier) pair a resolved bundle belongs to, for
+/// naming its offline-cache sidecar file.
+fn bundle_cache_key(product_type: &str, product_version: &str, mcc: &str, mnc: &str) -> String {
+    format!("{product_type}_{product_version}_{mcc}_{mnc}")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Reads back the catalog entry persisted by `persist_bundle_info` for
+/// `cache_key`, if any, so a previously resolved bundle can be verified and
+/// reused without querying the catalog.
+fn cached_bundle_info(cache_dir: &Path, cache_key: &str) -> Option<CarrierBundleInfo> {
+    let contents = fs::read_to_string(cache_dir.join(format!("{cache_key}.json"))).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Persists `bundle` next to its cached `.ipcc` file so it can be resolved
+/// offline on a later run.
+fn persist_bundle_info(cache_dir: &Path, cache_key: &str, bundle: &CarrierBundleInfo) {
+    if let Ok(json) = serde_json::to_string_pretty(bundle) {
+        let _ = fs::write(cache_dir.join(format!("{cache_key}.json")), json);
+    }
+}
+
+fn file_matches_checksum(path: &Path, expected: &str) -> Result<bool, String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    Ok(sha256_hex(&bytes) == expected)
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    format!("{:x}", hasher.finalize())
+}
+
+#[tauri::command]
+pub fn list_carrier_bundles(
+    product_type: String,
+    product_version: String,
+) -> Result<Vec<CarrierBundleInfo>, String> {
+    list_available_bundles(&product_type, &product_version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir() -> PathBuf {
+        super::super::test_support::scratch_dir("provisioning")
+    }
+
+    #[test]
+    fn sha256_hex_matches_known_digest() {
+        // sha256("") per the published test vector.
+        assert_eq!(
+            sha256_hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn file_matches_checksum_true_for_matching_digest() {
+        let dir = scratch_dir();
+        let path = dir.join("bundle.ipcc");
+        fs::write(&path, b"bundle bytes").unwrap();
+
+        let expected = sha256_hex(b"bundle bytes");
+        assert!(file_matches_checksum(&path, &expected).unwrap());
+    }
+
+    #[test]
+    fn file_matches_checksum_false_for_mismatched_digest() {
+        let dir = scratch_dir();
+        let path = dir.join("bundle.ipcc");
+        fs::write(&path, b"bundle bytes").unwrap();
+
+        assert!(!file_matches_checksum(&path, "not-the-real-digest").unwrap());
+    }
+
+    #[test]
+    fn bundle_cache_key_sanitizes_non_alphanumeric_characters() {
+        assert_eq!(
+            bundle_cache_key("iPhone14,5", "17.1", "310", "260"),
+            "iPhone14_5_17_1_310_260"
+        );
+    }
+
+    #[test]
+    fn persisted_bundle_info_round_trips_through_cached_bundle_info() {
+        let dir = scratch_dir();
+        let bundle = CarrierBundleInfo {
+            id: "bundle-1".to_string(),
+            carrier_name: "Test Carrier".to_string(),
+            mcc: "310".to_string(),
+            mnc: "260".to_string(),
+            product_type: "iPhone14,5".to_string(),
+            product_version: "17.1".to_string(),
+            download_url: "https://example.com/bundle-1.ipcc".to_string(),
+            sha256: "deadbeef".to_string(),
+        };
+
+        persist_bundle_info(&dir, "key", &bundle);
+
+        let reloaded = cached_bundle_info(&dir, "key").expect("manifest should round-trip");
+        assert_eq!(reloaded.id, bundle.id);
+        assert_eq!(reloaded.sha256, bundle.sha256);
+    }
+
+    #[test]
+    fn cached_bundle_info_is_none_when_no_manifest_written() {
+        let dir = scratch_dir();
+        assert!(cached_bundle_info(&dir, "missing").is_none());
+    }
+}
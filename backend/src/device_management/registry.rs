@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use rsmobiledevice::device::DeviceClient;
+
+/// A registry-managed device client, guarded by its own mutex so at most one
+/// operation runs against a given device's connection at a time. Wrapping in
+/// `Mutex` also means sharing a handle across threads only ever needs
+/// `DeviceClient: Send`, not `Sync`.
+pub type DeviceHandle<T = DeviceClient> = Arc<Mutex<T>>;
+
+/// Locks `handle`, recovering the guard even if a previous operation panicked
+/// while holding it instead of propagating the poison. Device FFI calls can
+/// panic mid-operation (a yanked cable, a bad device state); without this,
+/// one panic would poison the handle's mutex and every later command for
+/// that UDID would panic too until the device fully disconnects and
+/// reconnects, which defeats the registry's whole point of staying resilient
+/// to exactly that kind of flakiness.
+pub fn lock_device<T>(handle: &DeviceHandle<T>) -> MutexGuard<'_, T> {
+    handle.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Tracks every currently connected device by UDID.
+///
+/// `check_device` keeps this in sync on `Event::Connect`/`Event::Disconnect`,
+/// and commands that used to reach for `get_first_device()` should instead
+/// take a `udid` argument and look the device up here so multiple attached
+/// iPhones can be diagnosed and flashed independently. Locking a device's
+/// `DeviceHandle` for the duration of an operation also keeps two commands
+/// (e.g. a background install and a status check) from racing on the same
+/// physical device's single connection.
+///
+/// Generic over the client type so tests can swap in a fake; production code
+/// always uses the default `DeviceClient`.
+///
+/// Managed as `tauri::State<DeviceRegistry>`, e.g. `.manage(DeviceRegistry::default())`
+/// in the app builder.
+pub struct DeviceRegistry<T = DeviceClient>(Mutex<HashMap<String, DeviceHandle<T>>>);
+
+impl<T> Default for DeviceRegistry<T> {
+    fn default() -> Self {
+        Self(Mutex::new(HashMap::new()))
+    }
+}
+
+impl<T> DeviceRegistry<T> {
+    pub fn insert(&self, udid: String, client: T) {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(udid, Arc::new(Mutex::new(client)));
+    }
+
+    pub fn get(&self, udid: &str) -> Option<DeviceHandle<T>> {
+        self.0.lock().unwrap().get(udid).cloned()
+    }
+
+    /// UDIDs of every device currently tracked in the registry.
+    pub fn udids(&self) -> Vec<String> {
+        self.0.lock().unwrap().keys().cloned().collect()
+    }
+
+    /// Drops every entry whose UDID is not in `connected`, returning the
+    /// UDIDs that were removed.
+    pub fn retain_connected(&self, connected: &[String]) -> Vec<String> {
+        let mut map = self.0.lock().unwrap();
+        let stale: Vec<String> = map
+            .keys()
+            .filter(|udid| !connected.contains(udid))
+            .cloned()
+            .collect();
+        for udid in &stale {
+            map.remove(udid);
+        }
+        stale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry_with(udids: &[&str]) -> DeviceRegistry<u32> {
+        let registry = DeviceRegistry::default();
+        for (i, udid) in udids.iter().enumerate() {
+            registry.insert(udid.to_string(), i as u32);
+        }
+        registry
+    }
+
+    #[test]
+    fn get_returns_none_for_unknown_udid() {
+        let registry: DeviceRegistry<u32> = DeviceRegistry::default();
+        assert!(registry.get("unknown").is_none());
+    }
+
+    #[test]
+    fn insert_then_get_returns_same_device() {
+        let registry = registry_with(&["udid-1"]);
+        let handle = registry.get("udid-1").expect("device should be tracked");
+        assert_eq!(*handle.lock().unwrap(), 0);
+        assert_eq!(registry.udids(), vec!["udid-1".to_string()]);
+    }
+
+    #[test]
+    fn retain_connected_drops_stale_entries_and_reports_them() {
+        let registry = registry_with(&["udid-1", "udid-2"]);
+
+        let mut removed = registry.retain_connected(&["udid-1".to_string()]);
+        removed.sort();
+
+        assert_eq!(removed, vec!["udid-2".to_string()]);
+        assert!(registry.get("udid-1").is_some());
+        assert!(registry.get("udid-2").is_none());
+    }
+
+    #[test]
+    fn retain_connected_with_everything_connected_removes_nothing() {
+        let registry = registry_with(&["udid-1", "udid-2"]);
+        let removed = registry.retain_connected(&["udid-1".to_string(), "udid-2".to_string()]);
+        assert!(removed.is_empty());
+        assert_eq!(registry.udids().len(), 2);
+    }
+}
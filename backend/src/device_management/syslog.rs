@@ -0,0 +1,316 @@
+use regex::Regex;
+use rsmobiledevice::{
+    device::DeviceClient,
+    device_syslog::{filters::FilterPart, DeviceSyslog, LogFilter},
+};
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::State;
+
+use super::events::emit_for_device;
+use super::registry::{lock_device, DeviceRegistry};
+
+/// How many recent lines to keep per device so the UI can backfill history
+/// for a stream it only just attached to.
+const RING_BUFFER_CAPACITY: usize = 500;
+
+/// Which part of a syslog line a filter's regex is matched against.
+/// Mirrors `rsmobiledevice::device_syslog::filters::FilterPart` so it can be
+/// passed across the Tauri command boundary (which requires `Deserialize`).
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SyslogFilterPart {
+    ProcessName,
+    Message,
+    All,
+}
+
+impl From<SyslogFilterPart> for FilterPart {
+    fn from(part: SyslogFilterPart) -> Self {
+        match part {
+            SyslogFilterPart::ProcessName => FilterPart::ProcessName,
+            SyslogFilterPart::Message => FilterPart::Message,
+            SyslogFilterPart::All => FilterPart::All,
+        }
+    }
+}
+
+/// A single caller-supplied filter: a regex pattern plus which part of the
+/// line it applies to.
+#[derive(Deserialize, Clone, Debug)]
+pub struct SyslogFilter {
+    pub pattern: String,
+    pub part: SyslogFilterPart,
+}
+
+/// `set_filter` replaces the active filter for a given `FilterPart` rather
+/// than adding to it, so applying `filters` one at a time would leave only
+/// the last one in effect. Instead, group them by part and OR their patterns
+/// together into one regex per part, so e.g. two `Message` filters both stay
+/// active alongside a third on `ProcessName`.
+fn combine_filters_by_part(filters: &[SyslogFilter]) -> Vec<(SyslogFilterPart, String)> {
+    let mut grouped: Vec<(SyslogFilterPart, Vec<&str>)> = Vec::new();
+    for filter in filters {
+        match grouped.iter_mut().find(|(part, _)| *part == filter.part) {
+            Some((_, patterns)) => patterns.push(&filter.pattern),
+            None => grouped.push((filter.part, vec![&filter.pattern])),
+        }
+    }
+
+    grouped
+        .into_iter()
+        .map(|(part, patterns)| {
+            let combined = patterns
+                .into_iter()
+                .map(|pattern| format!("(?:{pattern})"))
+                .collect::<Vec<_>>()
+                .join("|");
+            (part, combined)
+        })
+        .collect()
+}
+
+/// Pushes `line` onto `buffer`, evicting the oldest entry first if `buffer`
+/// is already at `capacity`.
+fn push_bounded(buffer: &mut VecDeque<String>, line: String, capacity: usize) {
+    if buffer.len() == capacity {
+        buffer.pop_front();
+    }
+    buffer.push_back(line);
+}
+
+/// A live streaming session for one device: the ring buffer the UI can read
+/// on attach, a flag used to stop the Rust-side callback from emitting any
+/// more lines, and the syslog client handle itself. Holding onto the handle
+/// for the session's lifetime (instead of letting it drop at the end of
+/// `start_syslog_stream`) means the native log relay it owns is actually
+/// torn down when the session is removed, rather than continuing to stream
+/// into a callback nothing reads from anymore.
+///
+/// Generic over the client type so tests can swap in a fake; production code
+/// always uses the default `DeviceSyslog`.
+struct SyslogSession<T = DeviceSyslog> {
+    buffer: Arc<Mutex<VecDeque<String>>>,
+    stopped: Arc<std::sync::atomic::AtomicBool>,
+    #[allow(dead_code)]
+    syslog_client: T,
+}
+
+/// Tracks the in-progress `start_syslog_stream` sessions, keyed by UDID.
+/// Managed as `tauri::State<SyslogSessions>`.
+pub struct SyslogSessions<T = DeviceSyslog>(Mutex<HashMap<String, SyslogSession<T>>>);
+
+impl<T> Default for SyslogSessions<T> {
+    fn default() -> Self {
+        Self(Mutex::new(HashMap::new()))
+    }
+}
+
+impl<T> SyslogSessions<T> {
+    /// Removes and drops `udid`'s session, which stops the Rust-side
+    /// callback and tears down its syslog client handle.
+    fn stop(&self, udid: &str) {
+        if let Some(session) = self.0.lock().unwrap().remove(udid) {
+            session.stopped.store(true, std::sync::atomic::Ordering::SeqCst);
+            drop(session);
+        }
+    }
+}
+
+#[tauri::command]
+pub fn start_syslog_stream(
+    window: tauri::Window,
+    device_registry: State<DeviceRegistry>,
+    sessions: State<SyslogSessions>,
+    udid: String,
+    filters: Vec<SyslogFilter>,
+) -> Result<(), String> {
+    sessions.stop(&udid);
+
+    let device_handle = device_registry
+        .get(&udid)
+        .ok_or_else(|| format!("No tracked device for UDID {udid}"))?;
+    let device_client = lock_device(&device_handle);
+
+    let mut syslog_client = device_client.get_device_syslog();
+    for (part, pattern) in combine_filters_by_part(&filters) {
+        let regex = Regex::new(&pattern).map_err(|e| e.to_string())?;
+        syslog_client.set_filter(LogFilter::Continuous(regex), part.into());
+    }
+
+    let buffer = Arc::new(Mutex::new(VecDeque::with_capacity(RING_BUFFER_CAPACITY)));
+    let stopped = Arc::new(std::sync::atomic::AtomicBool::new(false));
+
+    let buffer_clone = Arc::clone(&buffer);
+    let stopped_clone = Arc::clone(&stopped);
+    let udid_clone = udid.clone();
+
+    syslog_client
+        .log_to_custom(move |line: String| {
+            if stopped_clone.load(std::sync::atomic::Ordering::SeqCst) {
+                return;
+            }
+
+            let mut buffer = buffer_clone.lock().unwrap();
+            push_bounded(&mut buffer, line.clone(), RING_BUFFER_CAPACITY);
+            drop(buffer);
+
+            emit_for_device(&window, "syslog_line", &udid_clone, &line);
+        })
+        .map_err(|e| e.to_string())?;
+
+    sessions.0.lock().unwrap().insert(
+        udid,
+        SyslogSession {
+            buffer,
+            stopped,
+            syslog_client,
+        },
+    );
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_syslog_stream(sessions: State<SyslogSessions>, udid: String) {
+    sessions.stop(&udid);
+}
+
+/// Snapshot of the most recent lines buffered for `udid`, e.g. for a UI that
+/// just attached and wants history instead of starting from a blank pane.
+#[tauri::command]
+pub fn syslog_history(sessions: State<SyslogSessions>, udid: String) -> Vec<String> {
+    sessions
+        .0
+        .lock()
+        .unwrap()
+        .get(&udid)
+        .map(|session| session.buffer.lock().unwrap().iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Runs a single-shot filtered syslog scan: calls `on_match` the first time
+/// `pattern` matches `part` of a line, or `on_timeout` if nothing matches
+/// within `timeout`. Used for one-off detections like "did the SIM come up"
+/// rather than an ongoing stream.
+pub fn run_oneshot_filter<F, G>(
+    device_client: &DeviceClient,
+    pattern: &str,
+    part: FilterPart,
+    timeout: Duration,
+    on_match: F,
+    on_timeout: G,
+) -> Result<(), String>
+where
+    F: FnOnce(String) + Send + 'static,
+    G: FnOnce() + Send + 'static,
+{
+    let mut syslog_client = device_client.get_device_syslog();
+    let regex = Regex::new(pattern).map_err(|e| e.to_string())?;
+    syslog_client.set_filter(LogFilter::OneShot(regex), part);
+
+    syslog_client
+        .log_to_custom_with_timeout_or_else(on_match, timeout, on_timeout)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(pattern: &str, part: SyslogFilterPart) -> SyslogFilter {
+        SyslogFilter {
+            pattern: pattern.to_string(),
+            part,
+        }
+    }
+
+    #[test]
+    fn combine_filters_by_part_groups_same_part_and_keeps_parts_separate() {
+        let filters = vec![
+            filter("sim ready", SyslogFilterPart::Message),
+            filter("CommCenter", SyslogFilterPart::ProcessName),
+            filter("no service", SyslogFilterPart::Message),
+        ];
+
+        let grouped = combine_filters_by_part(&filters);
+
+        assert_eq!(grouped.len(), 2);
+        let message = &grouped.iter().find(|(part, _)| *part == SyslogFilterPart::Message).unwrap().1;
+        assert_eq!(message, "(?:sim ready)|(?:no service)");
+        let process_name = &grouped
+            .iter()
+            .find(|(part, _)| *part == SyslogFilterPart::ProcessName)
+            .unwrap()
+            .1;
+        assert_eq!(process_name, "(?:CommCenter)");
+    }
+
+    #[test]
+    fn combined_regex_matches_every_filter_it_was_built_from() {
+        let filters = vec![
+            filter("sim ready", SyslogFilterPart::Message),
+            filter("no service", SyslogFilterPart::Message),
+        ];
+
+        let (_, pattern) = &combine_filters_by_part(&filters)[0];
+        let regex = Regex::new(pattern).unwrap();
+
+        assert!(regex.is_match("sim ready detected"));
+        assert!(regex.is_match("device reports no service"));
+        assert!(!regex.is_match("unrelated log line"));
+    }
+
+    #[test]
+    fn syslog_filter_part_maps_to_matching_filter_part_variant() {
+        assert!(matches!(FilterPart::from(SyslogFilterPart::ProcessName), FilterPart::ProcessName));
+        assert!(matches!(FilterPart::from(SyslogFilterPart::Message), FilterPart::Message));
+        assert!(matches!(FilterPart::from(SyslogFilterPart::All), FilterPart::All));
+    }
+
+    #[test]
+    fn push_bounded_keeps_buffer_within_capacity() {
+        let mut buffer = VecDeque::new();
+        for i in 0..5 {
+            push_bounded(&mut buffer, i.to_string(), 3);
+        }
+
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer, VecDeque::from(["2".to_string(), "3".to_string(), "4".to_string()]));
+    }
+
+    #[test]
+    fn push_bounded_under_capacity_does_not_evict() {
+        let mut buffer = VecDeque::new();
+        push_bounded(&mut buffer, "only".to_string(), 3);
+
+        assert_eq!(buffer, VecDeque::from(["only".to_string()]));
+    }
+
+    #[test]
+    fn sessions_stop_removes_entry_and_signals_the_background_thread() {
+        let sessions: SyslogSessions<u32> = SyslogSessions::default();
+        let stopped = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        sessions.0.lock().unwrap().insert(
+            "udid-1".to_string(),
+            SyslogSession {
+                buffer: Arc::new(Mutex::new(VecDeque::new())),
+                stopped: Arc::clone(&stopped),
+                syslog_client: 0,
+            },
+        );
+
+        sessions.stop("udid-1");
+
+        assert!(stopped.load(std::sync::atomic::Ordering::SeqCst));
+        assert!(sessions.0.lock().unwrap().get("udid-1").is_none());
+    }
+
+    #[test]
+    fn sessions_stop_on_unknown_udid_is_a_no_op() {
+        let sessions: SyslogSessions<u32> = SyslogSessions::default();
+        sessions.stop("unknown");
+    }
+}
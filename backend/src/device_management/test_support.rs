@@ -0,0 +1,18 @@
+//! Helpers shared by this module's `#[cfg(test)]` blocks.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TEST_DIR_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// A fresh, empty scratch directory under the system temp dir, unique per
+/// call so tests running in parallel don't collide. `prefix` just makes the
+/// directory name traceable back to the test module that created it.
+pub fn scratch_dir(prefix: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "openitool_{prefix}_test_{}",
+        TEST_DIR_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}